@@ -0,0 +1,249 @@
+//! The authenticated account used to place and manage orders.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use openssl::pkey::{PKey, Private};
+use serde_json::json;
+
+use crate::api::{
+    ApiDirectory, ApiIdentifier, ApiKeyChange, ApiOrder, ApiOrderResponse, ApiRevocation,
+    ApiStatusChange,
+};
+use crate::cert::{create_p384_key, Certificate};
+use crate::error::{Error, Result};
+use crate::jwt;
+use crate::order::NewOrder;
+use crate::persist::{Persist, PersistKind};
+use crate::trans::{NonceSource, Transaction};
+
+/// Why a certificate is being revoked, per RFC 5280 §5.3.1. Only the
+/// reasons an ACME client would plausibly use are exposed.
+#[derive(Debug, Clone, Copy)]
+pub enum RevocationReason {
+    Unspecified = 0,
+    KeyCompromise = 1,
+    Superseded = 4,
+    CessationOfOperation = 5,
+}
+
+/// A registered ACME account.
+pub struct Account {
+    pkey: PKey<Private>,
+    account_url: String,
+    contact: Vec<String>,
+    dir: ApiDirectory,
+    nonces: Arc<NonceSource>,
+    realm: String,
+    persist: Option<Arc<dyn Persist>>,
+}
+
+impl Account {
+    pub(crate) fn new(
+        pkey: PKey<Private>,
+        account_url: String,
+        contact: Vec<String>,
+        dir: ApiDirectory,
+        nonces: Arc<NonceSource>,
+        realm: String,
+        persist: Option<Arc<dyn Persist>>,
+    ) -> Self {
+        Account {
+            pkey,
+            account_url,
+            contact,
+            dir,
+            nonces,
+            realm,
+            persist,
+        }
+    }
+
+    fn trans(&self) -> Transaction<'_> {
+        Transaction {
+            pkey: &self.pkey,
+            account_url: Some(&self.account_url),
+            nonces: &self.nonces,
+        }
+    }
+
+    /// The account private key, PEM encoded. Save this to disk and use it
+    /// with [`crate::Directory::load_account`] to avoid re-registering.
+    pub async fn acme_private_key_pem(&self) -> Result<String> {
+        let pem = self.pkey.private_key_to_pem_pkcs8()?;
+        Ok(String::from_utf8_lossy(&pem).to_string())
+    }
+
+    /// Place a new order for `domain` plus any `alt_names`.
+    pub async fn new_order(&self, domain: &str, alt_names: &[&str]) -> Result<NewOrder> {
+        let mut identifiers = vec![ApiIdentifier {
+            typ: "dns".into(),
+            value: domain.to_string(),
+        }];
+        identifiers.extend(alt_names.iter().map(|d| ApiIdentifier {
+            typ: "dns".into(),
+            value: d.to_string(),
+        }));
+
+        let payload = ApiOrder { identifiers };
+        let res = self.trans().post(&self.dir.new_order, &payload).await?;
+        let order_url = res
+            .location
+            .ok_or_else(|| Error::Other("newOrder response had no Location header".into()))?;
+        let api: ApiOrderResponse = serde_json::from_str(&res.body)?;
+
+        Ok(NewOrder::new(
+            order_url,
+            api,
+            self.pkey.clone(),
+            self.account_url.clone(),
+            self.nonces.clone(),
+            self.realm.clone(),
+            self.persist.clone(),
+        ))
+    }
+
+    /// Revoke a previously issued certificate.
+    pub async fn revoke_certificate(
+        &self,
+        cert: &Certificate,
+        reason: Option<RevocationReason>,
+    ) -> Result<()> {
+        let der = openssl::x509::X509::from_pem(cert.certificate().as_bytes())?.to_der()?;
+        let payload = ApiRevocation {
+            certificate: crate::util::b64(&der),
+            reason: reason.map(|r| r as u32),
+        };
+        self.trans().post(&self.dir.revoke_cert, &payload).await?;
+        Ok(())
+    }
+
+    /// Renew `existing` if fewer than `threshold` of its validity remains,
+    /// otherwise return it unchanged. This makes it cheap for a long-running
+    /// server to poll regularly without hitting the ACME API (and its rate
+    /// limits) on every check.
+    ///
+    /// Renewal only succeeds without further action from the caller if the
+    /// CA still considers `domains` authorized from a previous order (see
+    /// [`NewOrder::confirm_validations`]); otherwise this returns an error
+    /// and the caller should drive [`Account::new_order`] directly to solve
+    /// a fresh challenge.
+    pub async fn renew_if_due(
+        &self,
+        existing: &Certificate,
+        domains: &[&str],
+        threshold: Duration,
+        delay: Duration,
+    ) -> Result<Certificate> {
+        if domains.is_empty() {
+            return Err(Error::Other(
+                "renew_if_due requires at least one domain".into(),
+            ));
+        }
+
+        if !existing.is_expired_within(threshold)? {
+            return Ok(existing.clone());
+        }
+
+        let order = self.new_order(domains[0], &domains[1..]).await?;
+        let csr_order = order.confirm_validations().await.ok_or_else(|| {
+            Error::Other(
+                "renewal requires solving a new challenge; drive Account::new_order directly"
+                    .into(),
+            )
+        })?;
+
+        let pkey_pri = create_p384_key()?;
+        let cert_order = csr_order.finalize_pkey(pkey_pri, delay).await?;
+        cert_order.download_cert().await
+    }
+
+    /// Roll this account over to `new_pkey` (RFC 8555 §7.3.5). The new key
+    /// signs an inner JWS vouching for itself and the old key, wrapped in
+    /// an outer JWS signed with the current key; once the CA accepts it,
+    /// this `Account` signs all further requests with `new_pkey`.
+    pub async fn change_key(&mut self, new_pkey: PKey<Private>) -> Result<()> {
+        let inner_protected = json!({
+            "alg": jwt::alg(&new_pkey)?,
+            "jwk": jwt::jwk(&new_pkey)?,
+            "url": self.dir.key_change,
+        });
+        let inner_payload = ApiKeyChange {
+            account: self.account_url.clone(),
+            old_key: jwt::jwk(&self.pkey)?,
+        };
+        let inner_jws = jwt::sign_jws(&new_pkey, &inner_protected, &inner_payload)?;
+        let inner: serde_json::Value = serde_json::from_str(&inner_jws)?;
+
+        self.trans().post(&self.dir.key_change, &inner).await?;
+
+        // The CA has now rolled over to `new_pkey`. Swap it in before
+        // attempting to persist, so a failure below doesn't leave this
+        // `Account` signing with a key the CA no longer accepts.
+        let pem = new_pkey.private_key_to_pem_pkcs8()?;
+        self.pkey = new_pkey;
+
+        if let Some(persist) = &self.persist {
+            crate::persist::put_blocking(
+                persist.clone(),
+                self.realm.clone(),
+                PersistKind::AccountPrivateKey,
+                crate::dir::account_persist_key(&self.contact),
+                pem,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Deactivate this account (RFC 8555 §7.3.6). The CA will no longer
+    /// accept new orders signed with it.
+    pub async fn deactivate(&self) -> Result<()> {
+        let payload = ApiStatusChange {
+            status: "deactivated".into(),
+        };
+        self.trans().post(&self.account_url, &payload).await?;
+        Ok(())
+    }
+
+    /// Look up a certificate previously stored by
+    /// [`crate::order::CertOrder::download_cert`] for `domain`, together
+    /// with its key as stored by [`crate::order::CsrOrder::finalize_pkey`].
+    /// Returns
+    /// `Ok(None)` if nothing is persisted yet, or if this account wasn't
+    /// built with a persist layer at all. Pairs with
+    /// [`Account::renew_if_due`] so a long-running caller can pick up where
+    /// it left off across restarts instead of placing a fresh order.
+    pub async fn load_certificate(&self, domain: &str) -> Result<Option<Certificate>> {
+        let Some(persist) = self.persist.clone() else {
+            return Ok(None);
+        };
+        let key_pem = crate::persist::get_blocking(
+            persist.clone(),
+            self.realm.clone(),
+            PersistKind::PrivateKey,
+            domain.to_string(),
+        )
+        .await?;
+        let cert_pem = crate::persist::get_blocking(
+            persist,
+            self.realm.clone(),
+            PersistKind::Certificate,
+            domain.to_string(),
+        )
+        .await?;
+        match (key_pem, cert_pem) {
+            (Some(key), Some(cert)) => Ok(Some(Certificate::new(
+                String::from_utf8_lossy(&key).to_string(),
+                String::from_utf8_lossy(&cert).to_string(),
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Contact addresses this account was registered with.
+    pub fn contact(&self) -> &[String] {
+        &self.contact
+    }
+}
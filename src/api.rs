@@ -0,0 +1,147 @@
+//! Wire types for the ACME JSON protocol (RFC 8555).
+//!
+//! These are plain serde structs mirroring the JSON bodies the ACME API
+//! sends and receives. Higher level, more ergonomic wrappers live in
+//! [`crate::order`] and [`crate::acc`].
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The directory document returned from the ACME API root.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiDirectory {
+    pub new_nonce: String,
+    pub new_account: String,
+    pub new_order: String,
+    pub new_authz: Option<String>,
+    pub revoke_cert: String,
+    pub key_change: String,
+}
+
+/// Request body for `newAccount`.
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiAccount {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terms_of_service_agreed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub only_return_existing: Option<bool>,
+    /// Set when registering against a CA that requires External Account
+    /// Binding (RFC 8555 §7.3.4), e.g. ZeroSSL or Google Certificate
+    /// Manager. This is itself a JWS in flattened JSON serialization,
+    /// embedded as a JSON object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_account_binding: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// The response body for an account resource.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiAccountResponse {
+    pub status: String,
+    #[serde(default)]
+    pub contact: Vec<String>,
+}
+
+/// Request body for `newOrder`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiOrder {
+    pub identifiers: Vec<ApiIdentifier>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiIdentifier {
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub value: String,
+}
+
+/// Response body for an order resource.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiOrderResponse {
+    pub status: String,
+    #[serde(default)]
+    pub identifiers: Vec<ApiIdentifier>,
+    pub authorizations: Vec<String>,
+    pub finalize: String,
+    #[serde(default)]
+    pub certificate: Option<String>,
+}
+
+/// Response body for an authorization resource.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiAuth {
+    pub identifier: ApiIdentifier,
+    pub status: String,
+    pub challenges: Vec<ApiChallenge>,
+}
+
+/// A single challenge inside an authorization.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiChallenge {
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub url: String,
+    pub token: String,
+    #[serde(default)]
+    pub status: String,
+}
+
+/// Body POSTed to a challenge url to kick off validation. Per RFC 8555 this
+/// is an empty JSON object.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ApiEmptyObject {}
+
+/// Body POSTed to the `finalize` url of an order.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiFinalize {
+    pub csr: String,
+}
+
+/// Body POSTed to `revokeCert`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiRevocation {
+    pub certificate: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<u32>,
+}
+
+/// Body POSTed to flip a resource's status (used for deactivation).
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiStatusChange {
+    pub status: String,
+}
+
+/// Inner payload of the `keyChange` JWS (RFC 8555 §7.3.5).
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiKeyChange {
+    pub account: String,
+    #[serde(rename = "oldKey")]
+    pub old_key: serde_json::Value,
+}
+
+/// A "problem document" as described by RFC 7807, returned by the ACME API
+/// on errors.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiProblem {
+    #[serde(rename = "type")]
+    pub typ: Option<String>,
+    pub detail: Option<String>,
+    pub status: Option<u16>,
+}
+
+impl fmt::Display for ApiProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({})",
+            self.detail.as_deref().unwrap_or("unknown problem"),
+            self.typ.as_deref().unwrap_or("about:blank")
+        )
+    }
+}
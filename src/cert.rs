@@ -0,0 +1,132 @@
+//! Key and certificate handling.
+
+use std::time::Duration;
+
+use openssl::asn1::Asn1Time;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::rsa::Rsa;
+use openssl::x509::{X509NameBuilder, X509Req, X509ReqBuilder, X509};
+
+use crate::error::Result;
+
+/// Create a new P-256 EC private key, suitable for an account key or a
+/// certificate key.
+pub fn create_p256_key() -> Result<PKey<Private>> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    let ec_key = EcKey::generate(&group)?;
+    Ok(PKey::from_ec_key(ec_key)?)
+}
+
+/// Create a new P-384 EC private key.
+pub fn create_p384_key() -> Result<PKey<Private>> {
+    let group = EcGroup::from_curve_name(Nid::SECP384R1)?;
+    let ec_key = EcKey::generate(&group)?;
+    Ok(PKey::from_ec_key(ec_key)?)
+}
+
+/// Create a new 2048 bit RSA private key.
+pub fn create_rsa_key() -> Result<PKey<Private>> {
+    let rsa = Rsa::generate(2048)?;
+    Ok(PKey::from_rsa(rsa)?)
+}
+
+/// Build a PKCS#10 CSR for `domains[0]` (as the CN) with all of `domains`
+/// as subjectAltNames, signed by `pkey`.
+pub fn create_csr(pkey: &PKey<Private>, domains: &[&str]) -> Result<X509Req> {
+    let mut builder = X509ReqBuilder::new()?;
+    builder.set_pubkey(pkey)?;
+
+    let mut name = X509NameBuilder::new()?;
+    name.append_entry_by_text("CN", domains[0])?;
+    builder.set_subject_name(&name.build())?;
+
+    let mut extensions = openssl::stack::Stack::new()?;
+    let ctx = builder.x509v3_context(None);
+    let mut san = openssl::x509::extension::SubjectAlternativeName::new();
+    for d in domains {
+        san.dns(d);
+    }
+    let san_ext = san.build(&ctx)?;
+    extensions.push(san_ext)?;
+    builder.add_extensions(&extensions)?;
+
+    builder.sign(pkey, openssl::hash::MessageDigest::sha384())?;
+    Ok(builder.build())
+}
+
+/// A successfully issued certificate: the full chain and the private key it
+/// was issued for, both PEM encoded.
+#[derive(Debug, Clone)]
+pub struct Certificate {
+    private_key: String,
+    certificate: String,
+}
+
+impl Certificate {
+    pub(crate) fn new(private_key: String, certificate: String) -> Self {
+        Certificate {
+            private_key,
+            certificate,
+        }
+    }
+
+    /// The PEM encoded private key the certificate was issued for.
+    pub fn private_key(&self) -> &str {
+        &self.private_key
+    }
+
+    /// The PEM encoded certificate chain, leaf first.
+    pub fn certificate(&self) -> &str {
+        &self.certificate
+    }
+
+    /// Parse the leaf (first) certificate in the chain.
+    fn leaf(&self) -> Result<X509> {
+        Ok(X509::from_pem(self.certificate.as_bytes())?)
+    }
+
+    /// The domains (from the leaf's subjectAltName) this certificate
+    /// covers.
+    pub fn domains(&self) -> Result<Vec<String>> {
+        let leaf = self.leaf()?;
+        Ok(leaf
+            .subject_alt_names()
+            .into_iter()
+            .flatten()
+            .filter_map(|name| name.dnsname().map(str::to_string))
+            .collect())
+    }
+
+    /// The leaf's `notBefore` field as openssl prints it, e.g.
+    /// `Jan  1 00:00:00 2025 GMT`.
+    pub fn not_before(&self) -> Result<String> {
+        Ok(self.leaf()?.not_before().to_string())
+    }
+
+    /// The leaf's `notAfter` field as openssl prints it, e.g.
+    /// `Jan  1 00:00:00 2025 GMT`.
+    pub fn not_after(&self) -> Result<String> {
+        Ok(self.leaf()?.not_after().to_string())
+    }
+
+    /// How many whole days of validity remain. Negative once the
+    /// certificate has expired.
+    pub fn valid_days_left(&self) -> Result<i32> {
+        let leaf = self.leaf()?;
+        let now = Asn1Time::days_from_now(0)?;
+        Ok(now.diff(leaf.not_after())?.days)
+    }
+
+    /// Whether fewer than `threshold` of validity remain, i.e. it's time to
+    /// renew.
+    pub fn is_expired_within(&self, threshold: Duration) -> Result<bool> {
+        let leaf = self.leaf()?;
+        let now = Asn1Time::days_from_now(0)?;
+        let diff = now.diff(leaf.not_after())?;
+        let secs_left = i64::from(diff.days) * 24 * 60 * 60 + i64::from(diff.secs);
+        let threshold_secs = i64::try_from(threshold.as_secs()).unwrap_or(i64::MAX);
+        Ok(secs_left < threshold_secs)
+    }
+}
@@ -0,0 +1,241 @@
+//! The ACME directory: the well-known entrypoint every flow starts from.
+
+use std::sync::Arc;
+
+use serde_json::json;
+
+use crate::acc::Account;
+use crate::api::{ApiAccount, ApiAccountResponse, ApiDirectory};
+use crate::cert::create_p384_key;
+use crate::error::{Error, Result};
+use crate::jwt;
+use crate::persist::{Persist, PersistKind};
+use crate::req;
+use crate::trans::{NonceSource, Transaction};
+use crate::util::b64_decode;
+
+/// Which ACME API to talk to.
+#[derive(Debug, Clone)]
+pub enum DirectoryUrl<'a> {
+    /// The production Let's Encrypt API.
+    LetsEncrypt,
+    /// The Let's Encrypt staging API. Use this while developing: its rate
+    /// limits are far more forgiving.
+    LetsEncryptStaging,
+    /// Any other ACME v2 compliant directory url.
+    Other(&'a str),
+}
+
+impl<'a> DirectoryUrl<'a> {
+    fn to_url(&self) -> &str {
+        match self {
+            DirectoryUrl::LetsEncrypt => "https://acme-v02.api.letsencrypt.org/directory",
+            DirectoryUrl::LetsEncryptStaging => {
+                "https://acme-staging-v02.api.letsencrypt.org/directory"
+            }
+            DirectoryUrl::Other(url) => url,
+        }
+    }
+}
+
+/// An ACME API entrypoint, holding the resource urls advertised by the
+/// server.
+pub struct Directory {
+    pub(crate) api: ApiDirectory,
+    pub(crate) nonces: Arc<NonceSource>,
+    pub(crate) realm: String,
+    pub(crate) persist: Option<Arc<dyn Persist>>,
+}
+
+impl Directory {
+    /// Fetch and parse the directory document.
+    pub async fn from_url(url: DirectoryUrl<'_>) -> Result<Directory> {
+        Self::from_url_impl(url, None).await
+    }
+
+    /// Like [`Directory::from_url`], but account registration, certificate
+    /// key creation and `download_cert` will also read/write through
+    /// `persist`.
+    pub async fn from_url_with_persist(
+        url: DirectoryUrl<'_>,
+        persist: Arc<dyn Persist>,
+    ) -> Result<Directory> {
+        Self::from_url_impl(url, Some(persist)).await
+    }
+
+    async fn from_url_impl(
+        url: DirectoryUrl<'_>,
+        persist: Option<Arc<dyn Persist>>,
+    ) -> Result<Directory> {
+        let url = url.to_url();
+        let res = req::get(url).await?;
+        let api: ApiDirectory = serde_json::from_str(&res.body)?;
+        let nonces = Arc::new(NonceSource::new(&api.new_nonce));
+        let realm = realm_of(url);
+        Ok(Directory {
+            api,
+            nonces,
+            realm,
+            persist,
+        })
+    }
+
+    /// Register a new account, agreeing to the CA's terms of service.
+    pub async fn register_account(&self, contact: Vec<String>) -> Result<Account> {
+        let pkey = create_p384_key()?;
+        let payload = ApiAccount {
+            contact: Some(contact.clone()),
+            terms_of_service_agreed: Some(true),
+            ..Default::default()
+        };
+        self.register_account_with_payload(pkey, contact, payload)
+            .await
+    }
+
+    /// Like [`Directory::register_account`] but binds the new account to an
+    /// existing account at the CA via External Account Binding (RFC 8555
+    /// §7.3.4), as required by CAs such as ZeroSSL or Google Certificate
+    /// Manager. `kid` and `hmac_key_b64` (base64url encoded) are the EAB
+    /// credentials issued out-of-band by the CA.
+    pub async fn register_account_with_eab(
+        &self,
+        contact: Vec<String>,
+        kid: &str,
+        hmac_key_b64: &str,
+    ) -> Result<Account> {
+        let pkey = create_p384_key()?;
+        let mac_key = b64_decode(hmac_key_b64)?;
+
+        let eab_protected = json!({
+            "alg": "HS256",
+            "kid": kid,
+            "url": self.api.new_account,
+        });
+        let eab_payload = jwt::jwk(&pkey)?;
+        let eab_jws = jwt::sign_jws_hmac(&mac_key, &eab_protected, &eab_payload)?;
+        let eab: serde_json::Value = serde_json::from_str(&eab_jws)?;
+
+        let payload = ApiAccount {
+            contact: Some(contact.clone()),
+            terms_of_service_agreed: Some(true),
+            external_account_binding: Some(eab),
+            ..Default::default()
+        };
+        self.register_account_with_payload(pkey, contact, payload)
+            .await
+    }
+
+    async fn register_account_with_payload(
+        &self,
+        pkey: openssl::pkey::PKey<openssl::pkey::Private>,
+        contact: Vec<String>,
+        payload: ApiAccount,
+    ) -> Result<Account> {
+        let trans = Transaction {
+            pkey: &pkey,
+            account_url: None,
+            nonces: &self.nonces,
+        };
+        let res = trans.post(&self.api.new_account, &payload).await?;
+        let account_url = res
+            .location
+            .ok_or_else(|| Error::Other("newAccount response had no Location header".into()))?;
+        let _account: ApiAccountResponse = serde_json::from_str(&res.body)?;
+
+        if let Some(persist) = &self.persist {
+            let pem = pkey.private_key_to_pem_pkcs8()?;
+            let account_key = account_persist_key(&contact);
+            crate::persist::put_blocking(
+                persist.clone(),
+                self.realm.clone(),
+                PersistKind::AccountPrivateKey,
+                account_key,
+                pem,
+            )
+            .await?;
+        }
+
+        Ok(Account::new(
+            pkey,
+            account_url,
+            contact,
+            self.api.clone(),
+            self.nonces.clone(),
+            self.realm.clone(),
+            self.persist.clone(),
+        ))
+    }
+
+    /// Load a previously registered account from its PEM encoded private
+    /// key. The account's url is looked up again via `onlyReturnExisting`.
+    pub async fn load_account(&self, privkey_pem: &str, contact: Vec<String>) -> Result<Account> {
+        let pkey = openssl::pkey::PKey::private_key_from_pem(privkey_pem.as_bytes())?;
+        let payload = ApiAccount {
+            only_return_existing: Some(true),
+            ..Default::default()
+        };
+        let trans = Transaction {
+            pkey: &pkey,
+            account_url: None,
+            nonces: &self.nonces,
+        };
+        let res = trans.post(&self.api.new_account, &payload).await?;
+        let account_url = res
+            .location
+            .ok_or_else(|| Error::Other("newAccount response had no Location header".into()))?;
+
+        Ok(Account::new(
+            pkey,
+            account_url,
+            contact,
+            self.api.clone(),
+            self.nonces.clone(),
+            self.realm.clone(),
+            self.persist.clone(),
+        ))
+    }
+
+    /// Like [`Directory::load_account`], but reads the account key back from
+    /// `persist` instead of requiring the caller to hold onto its PEM.
+    /// Returns `Ok(None)` if nothing has been persisted yet for `contact`,
+    /// in which case the caller should fall back to
+    /// [`Directory::register_account`].
+    pub async fn load_account_from_persist(
+        &self,
+        contact: Vec<String>,
+    ) -> Result<Option<Account>> {
+        let Some(persist) = self.persist.clone() else {
+            return Ok(None);
+        };
+        let account_key = account_persist_key(&contact);
+        let pem = crate::persist::get_blocking(
+            persist,
+            self.realm.clone(),
+            PersistKind::AccountPrivateKey,
+            account_key,
+        )
+        .await?;
+        let Some(pem) = pem else {
+            return Ok(None);
+        };
+        let pem = String::from_utf8_lossy(&pem).to_string();
+        Ok(Some(self.load_account(&pem, contact).await?))
+    }
+}
+
+/// The realm a set of persisted secrets is namespaced under: the ACME
+/// directory's host, so e.g. staging and production keys never collide.
+fn realm_of(directory_url: &str) -> String {
+    directory_url
+        .split("//")
+        .nth(1)
+        .and_then(|rest| rest.split('/').next())
+        .unwrap_or(directory_url)
+        .to_string()
+}
+
+/// The persist key used for an account's private key: accounts aren't
+/// keyed by a domain, so we use their contact addresses instead.
+pub(crate) fn account_persist_key(contact: &[String]) -> String {
+    contact.join(",")
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+
+use crate::api::ApiProblem;
+
+/// Result type used throughout the crate.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// All errors that can occur while talking to an ACME API.
+#[derive(Debug)]
+pub enum Error {
+    /// The ACME server returned a JSON "problem document".
+    Api(ApiProblem),
+    /// An openssl operation failed.
+    Tls(openssl::error::ErrorStack),
+    /// A network/HTTP layer error.
+    Call(String),
+    /// Failure to (de)serialize JSON.
+    Json(serde_json::Error),
+    /// Anything else, with a human readable message.
+    Other(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Api(problem) => write!(f, "api problem: {}", problem),
+            Error::Tls(e) => write!(f, "tls error: {}", e),
+            Error::Call(e) => write!(f, "call error: {}", e),
+            Error::Json(e) => write!(f, "json error: {}", e),
+            Error::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<openssl::error::ErrorStack> for Error {
+    fn from(e: openssl::error::ErrorStack) -> Self {
+        Error::Tls(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<base64::DecodeError> for Error {
+    fn from(e: base64::DecodeError) -> Self {
+        Error::Other(format!("base64 decode error: {}", e))
+    }
+}
+
+impl From<hyper::Error> for Error {
+    fn from(e: hyper::Error) -> Self {
+        Error::Call(e.to_string())
+    }
+}
+
+impl From<hyper_util::client::legacy::Error> for Error {
+    fn from(e: hyper_util::client::legacy::Error) -> Self {
+        Error::Call(e.to_string())
+    }
+}
+
+impl From<ApiProblem> for Error {
+    fn from(p: ApiProblem) -> Self {
+        Error::Api(p)
+    }
+}
@@ -0,0 +1,164 @@
+//! JWK/JWS helpers (RFC 7515, RFC 7638) used to sign every authenticated
+//! ACME request.
+
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::sign::Signer;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::error::{Error, Result};
+use crate::util::{b64, sha256};
+
+/// The JOSE/JWK parameters that vary with an EC key's curve: the JWK `crv`
+/// name, the JWS `alg` name, the digest to sign with, and the fixed byte
+/// width of each of the `r`/`s` signature coordinates (RFC 7518 §3.4).
+struct EcParams {
+    crv: &'static str,
+    alg: &'static str,
+    digest: MessageDigest,
+    coord_len: usize,
+}
+
+/// Only EC P-256/P-384 keys are produced by [`crate::cert::create_p256_key`]
+/// and [`crate::cert::create_p384_key`], which is all this crate needs to
+/// support.
+fn ec_params(pkey: &PKey<Private>) -> Result<EcParams> {
+    let ec_key = pkey.ec_key()?;
+    match ec_key.group().curve_name() {
+        Some(Nid::X9_62_PRIME256V1) => Ok(EcParams {
+            crv: "P-256",
+            alg: "ES256",
+            digest: MessageDigest::sha256(),
+            coord_len: 32,
+        }),
+        Some(Nid::SECP384R1) => Ok(EcParams {
+            crv: "P-384",
+            alg: "ES384",
+            digest: MessageDigest::sha384(),
+            coord_len: 48,
+        }),
+        other => Err(Error::Other(format!("unsupported EC curve: {:?}", other))),
+    }
+}
+
+/// The JWS `alg` to sign with this key, e.g. `"ES256"` for a P-256 key.
+pub(crate) fn alg(pkey: &PKey<Private>) -> Result<&'static str> {
+    Ok(ec_params(pkey)?.alg)
+}
+
+/// The JSON Web Key representation of an account/certificate key.
+pub fn jwk(pkey: &PKey<Private>) -> Result<serde_json::Value> {
+    let params = ec_params(pkey)?;
+    let ec_key = pkey.ec_key()?;
+    let group = ec_key.group();
+    let mut ctx = openssl::bn::BigNumContext::new()?;
+    let mut x = openssl::bn::BigNum::new()?;
+    let mut y = openssl::bn::BigNum::new()?;
+    ec_key
+        .public_key()
+        .affine_coordinates_gfp(group, &mut x, &mut y, &mut ctx)?;
+    Ok(json!({
+        "crv": params.crv,
+        "kty": "EC",
+        "x": b64(&x.to_vec()),
+        "y": b64(&y.to_vec()),
+    }))
+}
+
+/// The RFC 7638 JWK thumbprint: base64url(SHA-256(canonical JWK JSON)).
+pub fn jwk_thumbprint(pkey: &PKey<Private>) -> Result<String> {
+    let full = jwk(pkey)?;
+    // RFC 7638 requires exactly these members, lexicographically ordered.
+    let canonical = json!({
+        "crv": full["crv"],
+        "kty": full["kty"],
+        "x": full["x"],
+        "y": full["y"],
+    });
+    let digest = sha256(canonical.to_string().as_bytes())?;
+    Ok(b64(&digest))
+}
+
+/// Compute the ACME key authorization for a challenge token:
+/// `token || "." || base64url(JWK thumbprint)`.
+pub fn key_authorization(token: &str, pkey: &PKey<Private>) -> Result<String> {
+    Ok(format!("{}.{}", token, jwk_thumbprint(pkey)?))
+}
+
+fn sign(pkey: &PKey<Private>, signing_input: &[u8]) -> Result<Vec<u8>> {
+    let params = ec_params(pkey)?;
+    let mut signer = Signer::new(params.digest, pkey)?;
+    let der = signer.sign_oneshot_to_vec(signing_input)?;
+    // ACME wants the "raw" fixed-size r||s signature, not the DER ECDSA
+    // structure openssl gives us.
+    let sig = openssl::ecdsa::EcdsaSig::from_der(&der)?;
+    let r = sig.r().to_vec();
+    let s = sig.s().to_vec();
+    let n = params.coord_len;
+    let mut out = vec![0u8; n * 2];
+    out[n - r.len()..n].copy_from_slice(&r);
+    out[2 * n - s.len()..2 * n].copy_from_slice(&s);
+    Ok(out)
+}
+
+/// Build a JWS in the flattened JSON serialization ACME uses:
+/// `{"protected", "payload", "signature"}`, each base64url encoded, with the
+/// signature over `base64url(protected) || "." || base64url(payload)`.
+pub fn sign_jws<P: Serialize>(
+    pkey: &PKey<Private>,
+    protected: &serde_json::Value,
+    payload: &P,
+) -> Result<String> {
+    let protected = b64(protected.to_string().as_bytes());
+    let payload = b64(serde_json::to_string(payload)?.as_bytes());
+    let signing_input = format!("{}.{}", protected, payload);
+    let signature = b64(&sign(pkey, signing_input.as_bytes())?);
+    Ok(json!({
+        "protected": protected,
+        "payload": payload,
+        "signature": signature,
+    })
+    .to_string())
+}
+
+/// Like [`sign_jws`] but for a POST-as-GET or any request whose payload is
+/// the empty string (RFC 8555 §6.3).
+pub fn sign_jws_empty(pkey: &PKey<Private>, protected: &serde_json::Value) -> Result<String> {
+    let protected_b64 = b64(protected.to_string().as_bytes());
+    let payload_b64 = String::new();
+    let signing_input = format!("{}.{}", protected_b64, payload_b64);
+    let signature = b64(&sign(pkey, signing_input.as_bytes())?);
+    Ok(json!({
+        "protected": protected_b64,
+        "payload": payload_b64,
+        "signature": signature,
+    })
+    .to_string())
+}
+
+/// Build a flattened JSON JWS signed with HMAC-SHA256 instead of an asymmetric
+/// key, as used for the External Account Binding inner JWS (RFC 8555
+/// §7.3.4): the signature is HMAC-SHA256 over
+/// `base64url(protected) || "." || base64url(payload)` using `mac_key`.
+pub fn sign_jws_hmac<P: Serialize>(
+    mac_key: &[u8],
+    protected: &serde_json::Value,
+    payload: &P,
+) -> Result<String> {
+    let protected_b64 = b64(protected.to_string().as_bytes());
+    let payload_b64 = b64(serde_json::to_string(payload)?.as_bytes());
+    let signing_input = format!("{}.{}", protected_b64, payload_b64);
+
+    let key = PKey::hmac(mac_key)?;
+    let mut signer = Signer::new(openssl::hash::MessageDigest::sha256(), &key)?;
+    let signature = b64(&signer.sign_oneshot_to_vec(signing_input.as_bytes())?);
+
+    Ok(json!({
+        "protected": protected_b64,
+        "payload": payload_b64,
+        "signature": signature,
+    })
+    .to_string())
+}
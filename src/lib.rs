@@ -7,8 +7,12 @@
 //! # Example
 //!
 //! ```no_run
+//! # #[cfg(feature = "http-server")]
+//! # mod example {
 //! use hyperacme::{Error, Certificate, Directory, DirectoryUrl};
 //! use hyperacme::create_p384_key;
+//! use hyperacme::responder::Responder;
+//! use std::net::SocketAddr;
 //! use std::time::Duration;
 //!
 //! async fn request_cert() -> Result<Certificate, Error> {
@@ -46,38 +50,28 @@
 //!     // this will only be one element).
 //!     let auths = ord_new.authorizations().await?;
 //!
-//!     // For HTTP, the challenge is a text file that needs to
-//!     // be placed in your web server's root:
-//!     //
-//!     // /var/www/.well-known/acme-challenge/<token>
-//!     //
-//!     // The important thing is that it's accessible over the
-//!     // web for the domain(s) you are trying to get a
-//!     // certificate for:
+//!     // For HTTP, the challenge is a text file that needs to be
+//!     // accessible over the web for the domain(s) you are trying to
+//!     // get a certificate for:
 //!     //
 //!     // http://example.com/.well-known/acme-challenge/<token>
+//!     //
+//!     // If you don't already run a web server, `Responder` will serve
+//!     // it for you.
+//!     let chall = auths[0].http_challenge().await.unwrap();
+//!     let addr: SocketAddr = "0.0.0.0:80".parse().unwrap();
+//!     let mut responder = Responder::bind(addr, vec![chall]).await?;
 //!     let chall = auths[0].http_challenge().await.unwrap();
 //!
-//!     // The token is the filename.
-//!     let token = chall.http_token().await;
-//!     let path = format!(".well-known/acme-challenge/{}", token);
-//!
-//!     // The proof is the contents of the file
-//!     let proof = chall.http_proof().await?;
-//!
-//!     // Here you must do "something" to place
-//!     // the file/contents in the correct place.
-//!     // update_my_web_server(&path, &proof);
-//!
-//!     // After the file is accessible from the web, the calls
-//!     // this to tell the ACME API to start checking the
-//!     // existence of the proof.
+//!     // After the file is accessible from the web, tell the ACME API
+//!     // to start checking the existence of the proof.
 //!     //
 //!     // The order at ACME will change status to either
 //!     // confirm ownership of the domain, or fail due to the
 //!     // not finding the proof. To see the change, we poll
 //!     // the API with 5000 milliseconds wait between.
 //!     chall.validate(Duration::from_millis(5000)).await?;
+//!     responder.shutdown();
 //!
 //!     // Update the state against the ACME API.
 //!     ord_new.refresh().await?;
@@ -102,6 +96,7 @@
 //!
 //! Ok(cert)
 //! }
+//! # }
 //! ```
 //!
 //! ## Domain ownership
@@ -168,8 +163,11 @@ mod util;
 
 pub mod api;
 pub mod order;
+pub mod persist;
+
+#[cfg(feature = "http-server")]
+pub mod responder;
 
-#[macro_use]
 extern crate tracing;
 
 #[cfg(test)]
@@ -179,3 +177,4 @@ pub use crate::acc::{Account, RevocationReason};
 pub use crate::cert::{create_p256_key, create_p384_key, create_rsa_key, Certificate};
 pub use crate::dir::{Directory, DirectoryUrl};
 pub use crate::error::Error;
+pub use crate::persist::{FilePersist, Persist, PersistKey, PersistKind};
@@ -0,0 +1,485 @@
+//! Placing orders and proving domain ownership.
+//!
+//! The flow mirrors RFC 8555 §7.1: create an order, fetch its
+//! authorizations, satisfy a challenge for each, finalize with a CSR, then
+//! download the issued certificate.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use openssl::asn1::Asn1Time;
+use openssl::bn::{BigNum, MsbOption};
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::extension::SubjectAlternativeName;
+use openssl::x509::{X509NameBuilder, X509};
+
+use crate::api::{
+    ApiAuth, ApiChallenge, ApiEmptyObject, ApiFinalize, ApiOrderResponse, ApiStatusChange,
+};
+use crate::cert::{create_csr, create_p384_key, Certificate};
+use crate::error::{Error, Result};
+use crate::jwt::key_authorization;
+use crate::persist::{Persist, PersistKind};
+use crate::trans::{NonceSource, Transaction};
+use crate::util::{b64, sha256};
+
+/// OID for id-pe-acmeIdentifier (RFC 8737 §3), carried by the TLS-ALPN-01
+/// self-signed validation certificate.
+const ACME_IDENTIFIER_OID: &str = "1.3.6.1.5.5.7.1.31";
+
+/// The ALPN protocol id a validating server negotiates for TLS-ALPN-01
+/// (RFC 8737 §3).
+pub const TLS_ALPN_01_PROTOCOL: &str = "acme-tls/1";
+
+/// An order that has just been created and is awaiting validation.
+pub struct NewOrder {
+    order_url: String,
+    api: ApiOrderResponse,
+    pkey: PKey<Private>,
+    account_url: String,
+    nonces: Arc<NonceSource>,
+    realm: String,
+    persist: Option<Arc<dyn Persist>>,
+}
+
+impl NewOrder {
+    pub(crate) fn new(
+        order_url: String,
+        api: ApiOrderResponse,
+        pkey: PKey<Private>,
+        account_url: String,
+        nonces: Arc<NonceSource>,
+        realm: String,
+        persist: Option<Arc<dyn Persist>>,
+    ) -> Self {
+        NewOrder {
+            order_url,
+            api,
+            pkey,
+            account_url,
+            nonces,
+            realm,
+            persist,
+        }
+    }
+
+    fn trans(&self) -> Transaction<'_> {
+        Transaction {
+            pkey: &self.pkey,
+            account_url: Some(&self.account_url),
+            nonces: &self.nonces,
+        }
+    }
+
+    /// If the order is already `ready` (all authorizations already valid),
+    /// returns a [`CsrOrder`] ready for finalization. Otherwise returns
+    /// `None` and the caller should drive [`NewOrder::authorizations`].
+    pub async fn confirm_validations(&self) -> Option<CsrOrder> {
+        if self.api.status == "ready" {
+            Some(CsrOrder {
+                order_url: self.order_url.clone(),
+                finalize_url: self.api.finalize.clone(),
+                domains: self.api.identifiers.iter().map(|i| i.value.clone()).collect(),
+                pkey: self.pkey.clone(),
+                account_url: self.account_url.clone(),
+                nonces: self.nonces.clone(),
+                realm: self.realm.clone(),
+                persist: self.persist.clone(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Fetch the authorizations (one per identifier) that need a challenge
+    /// satisfied before the order can be finalized.
+    pub async fn authorizations(&self) -> Result<Vec<Auth>> {
+        let mut out = Vec::with_capacity(self.api.authorizations.len());
+        for url in &self.api.authorizations {
+            let res = self.trans().post_as_get(url).await?;
+            let api: ApiAuth = serde_json::from_str(&res.body)?;
+            out.push(Auth {
+                url: url.clone(),
+                api,
+                pkey: self.pkey.clone(),
+                account_url: self.account_url.clone(),
+                nonces: self.nonces.clone(),
+            });
+        }
+        Ok(out)
+    }
+
+    /// Re-fetch the order's current status from the ACME API.
+    pub async fn refresh(&mut self) -> Result<()> {
+        let res = self.trans().post_as_get(&self.order_url).await?;
+        self.api = serde_json::from_str(&res.body)?;
+        Ok(())
+    }
+}
+
+/// One identifier's authorization, offering a choice of challenges.
+pub struct Auth {
+    url: String,
+    api: ApiAuth,
+    pkey: PKey<Private>,
+    account_url: String,
+    nonces: Arc<NonceSource>,
+}
+
+impl Auth {
+    fn trans(&self) -> Transaction<'_> {
+        Transaction {
+            pkey: &self.pkey,
+            account_url: Some(&self.account_url),
+            nonces: &self.nonces,
+        }
+    }
+
+    fn challenge(&self, typ: &str) -> Option<Challenge> {
+        self.api
+            .challenges
+            .iter()
+            .find(|c| c.typ == typ)
+            .map(|c| Challenge {
+                api: c.clone(),
+                pkey: self.pkey.clone(),
+                account_url: self.account_url.clone(),
+                nonces: self.nonces.clone(),
+            })
+    }
+
+    /// The `http-01` challenge for this authorization, if the server
+    /// offered one.
+    pub async fn http_challenge(&self) -> Option<Challenge> {
+        self.challenge("http-01")
+    }
+
+    /// The `dns-01` challenge for this authorization, if the server offered
+    /// one.
+    pub async fn dns_challenge(&self) -> Option<Challenge> {
+        self.challenge("dns-01")
+    }
+
+    /// The `tls-alpn-01` challenge for this authorization, if the server
+    /// offered one (RFC 8737). Useful when neither port 80 nor the DNS
+    /// zone can be touched.
+    pub async fn tls_alpn_challenge(&self) -> Option<Challenge> {
+        self.challenge("tls-alpn-01")
+    }
+
+    /// The domain (or wildcard) this authorization covers.
+    pub fn domain(&self) -> &str {
+        &self.api.identifier.value
+    }
+
+    /// Deactivate this authorization (RFC 8555 §7.5.2), revoking the CA's
+    /// willingness to issue for its domain without a fresh challenge.
+    pub async fn deactivate(&self) -> Result<()> {
+        let payload = ApiStatusChange {
+            status: "deactivated".into(),
+        };
+        self.trans().post(&self.url, &payload).await?;
+        Ok(())
+    }
+}
+
+/// A single challenge, offering the material needed to satisfy it plus a
+/// way to tell the ACME server to check it.
+pub struct Challenge {
+    api: ApiChallenge,
+    pkey: PKey<Private>,
+    account_url: String,
+    nonces: Arc<NonceSource>,
+}
+
+impl Challenge {
+    fn trans(&self) -> Transaction<'_> {
+        Transaction {
+            pkey: &self.pkey,
+            account_url: Some(&self.account_url),
+            nonces: &self.nonces,
+        }
+    }
+
+    /// The key authorization: `token || "." || base64url(JWK thumbprint)`.
+    fn key_authorization(&self) -> Result<String> {
+        key_authorization(&self.api.token, &self.pkey)
+    }
+
+    /// The token, doubling as the `http-01` filename under
+    /// `/.well-known/acme-challenge/`.
+    pub async fn http_token(&self) -> String {
+        self.api.token.clone()
+    }
+
+    /// The contents to serve at
+    /// `/.well-known/acme-challenge/<http_token()>` for a `http-01`
+    /// challenge.
+    pub async fn http_proof(&self) -> Result<String> {
+        self.key_authorization()
+    }
+
+    /// The contents to publish at `_acme-challenge.<domain>` as a `TXT`
+    /// record for a `dns-01` challenge: base64url(SHA-256(key
+    /// authorization)).
+    pub async fn dns_proof(&self) -> Result<String> {
+        let digest = sha256(self.key_authorization()?.as_bytes())?;
+        Ok(b64(&digest))
+    }
+
+    /// Build the self-signed validation certificate (and its private key,
+    /// PEM encoded) that a `tls-alpn-01` responder must present when the
+    /// peer negotiates ALPN protocol [`TLS_ALPN_01_PROTOCOL`] with SNI set
+    /// to `domain` (RFC 8737 §3).
+    pub async fn tls_alpn_certificate(&self, domain: &str) -> Result<(String, String)> {
+        let key_auth = self.key_authorization()?;
+        let digest = sha256(key_auth.as_bytes())?;
+
+        let pkey = create_p384_key()?;
+
+        let mut name = X509NameBuilder::new()?;
+        name.append_entry_by_text("CN", domain)?;
+        let name = name.build();
+
+        let mut builder = X509::builder()?;
+        builder.set_version(2)?;
+        builder.set_subject_name(&name)?;
+        builder.set_issuer_name(&name)?;
+        builder.set_pubkey(&pkey)?;
+
+        let mut serial = BigNum::new()?;
+        serial.rand(64, MsbOption::MAYBE_ZERO, false)?;
+        let serial = serial.to_asn1_integer()?;
+        builder.set_serial_number(&serial)?;
+
+        builder.set_not_before(Asn1Time::days_from_now(0)?.as_ref())?;
+        builder.set_not_after(Asn1Time::days_from_now(7)?.as_ref())?;
+
+        let ctx = builder.x509v3_context(None, None);
+        let san = SubjectAlternativeName::new()
+            .dns(domain)
+            .build(&ctx)?;
+        builder.append_extension(san)?;
+
+        // id-pe-acmeIdentifier: a critical extension whose value is a DER
+        // OCTET STRING wrapping the 32-byte SHA-256 digest of the key
+        // authorization.
+        let octet_string = der_octet_string(&digest);
+        let oid = openssl::asn1::Asn1Object::from_str(ACME_IDENTIFIER_OID)?;
+        let value = openssl::asn1::Asn1OctetString::new_from_bytes(&octet_string)?;
+        let ext = openssl::x509::X509Extension::new_from_der(&oid, true, &value)?;
+        builder.append_extension(ext)?;
+
+        builder.sign(&pkey, MessageDigest::sha384())?;
+        let cert = builder.build();
+
+        let cert_pem = String::from_utf8_lossy(&cert.to_pem()?).to_string();
+        let key_pem =
+            String::from_utf8_lossy(&pkey.private_key_to_pem_pkcs8()?).to_string();
+        Ok((cert_pem, key_pem))
+    }
+
+    /// Tell the ACME server to check this challenge, then poll the
+    /// challenge every `delay` until it is `valid` (returns `Ok`) or
+    /// `invalid` (returns an error).
+    pub async fn validate(&self, delay: Duration) -> Result<()> {
+        self.trans().post(&self.api.url, &ApiEmptyObject {}).await?;
+
+        loop {
+            let res = self.trans().post_as_get(&self.api.url).await?;
+            let api: ApiChallenge = serde_json::from_str(&res.body)?;
+            match api.status.as_str() {
+                "valid" => return Ok(()),
+                "invalid" => {
+                    return Err(Error::Other(format!(
+                        "challenge {} failed validation",
+                        self.api.url
+                    )))
+                }
+                _ => tokio::time::sleep(delay).await,
+            }
+        }
+    }
+}
+
+/// A DER encoded `OCTET STRING` wrapping `data` (used to build the
+/// id-pe-acmeIdentifier extension value by hand, since openssl's extension
+/// builders don't know about it).
+fn der_octet_string(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x04u8, data.len() as u8];
+    out.extend_from_slice(data);
+    out
+}
+
+/// An order whose authorizations are all satisfied, ready to be finalized
+/// with a CSR.
+pub struct CsrOrder {
+    order_url: String,
+    finalize_url: String,
+    domains: Vec<String>,
+    pkey: PKey<Private>,
+    account_url: String,
+    nonces: Arc<NonceSource>,
+    realm: String,
+    persist: Option<Arc<dyn Persist>>,
+}
+
+impl CsrOrder {
+    fn trans(&self) -> Transaction<'_> {
+        Transaction {
+            pkey: &self.pkey,
+            account_url: Some(&self.account_url),
+            nonces: &self.nonces,
+        }
+    }
+
+    /// Submit a CSR built from `pkey_pri` (covering the order's domains),
+    /// then poll every `delay` until the CA has issued the certificate.
+    pub async fn finalize_pkey(
+        self,
+        pkey_pri: PKey<Private>,
+        delay: Duration,
+    ) -> Result<CertOrder> {
+        let domains: Vec<&str> = self.domains.iter().map(String::as_str).collect();
+        let req = create_csr(&pkey_pri, &domains)?;
+        let der = req.to_der()?;
+        let payload = ApiFinalize { csr: b64(&der) };
+        self.trans().post(&self.finalize_url, &payload).await?;
+
+        if let Some(persist) = &self.persist {
+            let pem = pkey_pri.private_key_to_pem_pkcs8()?;
+            crate::persist::put_blocking(
+                persist.clone(),
+                self.realm.clone(),
+                PersistKind::PrivateKey,
+                self.domains[0].clone(),
+                pem,
+            )
+            .await?;
+        }
+
+        loop {
+            let res = self.trans().post_as_get(&self.order_url).await?;
+            let api: ApiOrderResponse = serde_json::from_str(&res.body)?;
+            match api.status.as_str() {
+                "valid" => {
+                    return Ok(CertOrder {
+                        certificate_url: api
+                            .certificate
+                            .ok_or_else(|| Error::Other("order valid but no certificate url".into()))?,
+                        domain: self.domains[0].clone(),
+                        private_key: pkey_pri,
+                        pkey: self.pkey,
+                        account_url: self.account_url,
+                        nonces: self.nonces,
+                        realm: self.realm,
+                        persist: self.persist,
+                    })
+                }
+                "invalid" => return Err(Error::Other("order failed to finalize".into())),
+                _ => tokio::time::sleep(delay).await,
+            }
+        }
+    }
+}
+
+/// A finalized order, ready for the certificate to be downloaded.
+pub struct CertOrder {
+    certificate_url: String,
+    domain: String,
+    private_key: PKey<Private>,
+    pkey: PKey<Private>,
+    account_url: String,
+    nonces: Arc<NonceSource>,
+    realm: String,
+    persist: Option<Arc<dyn Persist>>,
+}
+
+impl CertOrder {
+    fn trans(&self) -> Transaction<'_> {
+        Transaction {
+            pkey: &self.pkey,
+            account_url: Some(&self.account_url),
+            nonces: &self.nonces,
+        }
+    }
+
+    /// Download the issued certificate chain.
+    pub async fn download_cert(self) -> Result<Certificate> {
+        let res = self.trans().post_as_get(&self.certificate_url).await?;
+
+        if let Some(persist) = &self.persist {
+            crate::persist::put_blocking(
+                persist.clone(),
+                self.realm.clone(),
+                PersistKind::Certificate,
+                self.domain.clone(),
+                res.body.clone().into_bytes(),
+            )
+            .await?;
+        }
+
+        let key_pem =
+            String::from_utf8_lossy(&self.private_key.private_key_to_pem_pkcs8()?).to_string();
+        Ok(Certificate::new(key_pem, res.body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::asn1::Asn1Object;
+    use openssl::x509::X509;
+
+    fn test_challenge() -> Challenge {
+        Challenge {
+            api: ApiChallenge {
+                typ: "tls-alpn-01".into(),
+                url: "https://acme.test/chall/1".into(),
+                token: "test-token".into(),
+                status: "pending".into(),
+            },
+            pkey: create_p384_key().unwrap(),
+            account_url: "https://acme.test/acct/1".into(),
+            nonces: Arc::new(NonceSource::new("https://acme.test/new-nonce")),
+        }
+    }
+
+    #[tokio::test]
+    async fn tls_alpn_certificate_carries_critical_acme_identifier_extension() {
+        let chall = test_challenge();
+        let expected_digest = sha256(chall.key_authorization().unwrap().as_bytes()).unwrap();
+
+        let (cert_pem, _key_pem) = chall.tls_alpn_certificate("example.com").await.unwrap();
+        let cert = X509::from_pem(cert_pem.as_bytes()).unwrap();
+
+        assert_eq!(
+            cert.subject_alt_names()
+                .into_iter()
+                .flatten()
+                .filter_map(|n| n.dnsname().map(str::to_string))
+                .collect::<Vec<_>>(),
+            vec!["example.com".to_string()]
+        );
+
+        // The safe openssl API has no generic "get extension by OID"
+        // lookup for X509 certs (only a handful of well-known ones), so
+        // confirm the critical id-pe-acmeIdentifier extension made it
+        // into the certificate by locating its own DER encoding --
+        // extnID, the critical marker, then the OCTET STRING wrapping
+        // the digest -- directly in the certificate's bytes.
+        let oid = Asn1Object::from_str(ACME_IDENTIFIER_OID).unwrap();
+        let oid_value = oid.as_slice();
+        let mut expected = vec![0x06u8, oid_value.len() as u8];
+        expected.extend_from_slice(oid_value);
+        expected.extend_from_slice(&[0x01, 0x01, 0xFF]); // critical: TRUE
+        expected.extend_from_slice(&der_octet_string(&der_octet_string(&expected_digest)));
+
+        let der = cert.to_der().unwrap();
+        assert!(
+            der.windows(expected.len()).any(|w| w == expected.as_slice()),
+            "certificate DER is missing the critical id-pe-acmeIdentifier extension"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! Pluggable persistence for account keys, certificate keys and issued
+//! certificates.
+//!
+//! Without a [`Persist`], callers have to manually shuttle PEM strings
+//! around themselves (see [`crate::Account::acme_private_key_pem`] and
+//! [`crate::Certificate`]). Passing one to
+//! [`crate::Directory::from_url_with_persist`] makes account registration,
+//! certificate key creation and `download_cert` store those automatically.
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+
+/// What kind of secret a [`PersistKey`] refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersistKind {
+    /// An account's private key.
+    AccountPrivateKey,
+    /// A certificate's private key.
+    PrivateKey,
+    /// An issued certificate chain.
+    Certificate,
+}
+
+impl PersistKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PersistKind::AccountPrivateKey => "key-account",
+            PersistKind::PrivateKey => "key-private",
+            PersistKind::Certificate => "crt",
+        }
+    }
+}
+
+/// Identifies a single persisted value.
+///
+/// `realm` namespaces keys by ACME API (so e.g. staging and production
+/// account keys don't collide), and `key` identifies the specific account
+/// or domain within that realm.
+pub struct PersistKey<'a> {
+    pub realm: &'a str,
+    pub kind: PersistKind,
+    pub key: &'a str,
+}
+
+impl<'a> PersistKey<'a> {
+    pub fn new(realm: &'a str, kind: PersistKind, key: &'a str) -> Self {
+        PersistKey { realm, kind, key }
+    }
+}
+
+/// A place to store and retrieve the secrets this crate produces.
+///
+/// Implementations must be safe to share across the `Directory`/`Account`
+/// hierarchy, which may hand out clones to concurrently running orders.
+pub trait Persist: fmt::Debug + Send + Sync {
+    fn put(&self, key: &PersistKey, value: &[u8]) -> Result<()>;
+    fn get(&self, key: &PersistKey) -> Result<Option<Vec<u8>>>;
+}
+
+/// A [`Persist`] that stores each value as a file in a directory.
+#[derive(Debug, Clone)]
+pub struct FilePersist {
+    dir: PathBuf,
+}
+
+impl FilePersist {
+    /// Store files under `dir`, creating it (and its parents) on first
+    /// write if it doesn't exist yet.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FilePersist { dir: dir.into() }
+    }
+
+    fn path(&self, key: &PersistKey) -> PathBuf {
+        let file_name = format!("{}_{}_{}.pem", key.realm, key.kind.as_str(), key.key);
+        self.dir.join(sanitize_file_name(&file_name))
+    }
+}
+
+impl Persist for FilePersist {
+    fn put(&self, key: &PersistKey, value: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.dir).map_err(|e| Error::Other(e.to_string()))?;
+        fs::write(self.path(key), value).map_err(|e| Error::Other(e.to_string()))
+    }
+
+    fn get(&self, key: &PersistKey) -> Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::Other(e.to_string())),
+        }
+    }
+}
+
+/// Run [`Persist::put`] on a blocking thread. Implementations like
+/// [`FilePersist`] do synchronous file I/O, and this crate is otherwise
+/// fully async, so callers must not invoke `Persist` methods directly from
+/// an async fn.
+pub(crate) async fn put_blocking(
+    persist: Arc<dyn Persist>,
+    realm: String,
+    kind: PersistKind,
+    key: String,
+    value: Vec<u8>,
+) -> Result<()> {
+    tokio::task::spawn_blocking(move || persist.put(&PersistKey::new(&realm, kind, &key), &value))
+        .await
+        .map_err(|e| Error::Other(e.to_string()))?
+}
+
+/// Run [`Persist::get`] on a blocking thread; see [`put_blocking`].
+pub(crate) async fn get_blocking(
+    persist: Arc<dyn Persist>,
+    realm: String,
+    kind: PersistKind,
+    key: String,
+) -> Result<Option<Vec<u8>>> {
+    tokio::task::spawn_blocking(move || persist.get(&PersistKey::new(&realm, kind, &key)))
+        .await
+        .map_err(|e| Error::Other(e.to_string()))?
+}
+
+/// Replace anything that isn't filesystem-safe with `_`, so realms/keys
+/// derived from urls or domains can't escape `dir`.
+fn sanitize_file_name(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,83 @@
+//! Thin wrapper around the `hyper` client used for all ACME API calls.
+
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper::{Method, Request, Response};
+use hyper_tls::HttpsConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+
+use crate::error::{Error, Result};
+
+type HttpClient = Client<HttpsConnector<hyper_util::client::legacy::connect::HttpConnector>, Full<Bytes>>;
+
+fn client() -> HttpClient {
+    let https = HttpsConnector::new();
+    Client::builder(TokioExecutor::new()).build(https)
+}
+
+/// A simplified response: status code, headers we care about, and the body
+/// read to completion.
+pub struct ReqResponse {
+    pub status: u16,
+    pub replay_nonce: Option<String>,
+    pub location: Option<String>,
+    pub body: String,
+}
+
+async fn to_req_response(res: Response<hyper::body::Incoming>) -> Result<ReqResponse> {
+    let status = res.status().as_u16();
+    let replay_nonce = res
+        .headers()
+        .get("replay-nonce")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    let location = res
+        .headers()
+        .get("location")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    let bytes = res.into_body().collect().await?.to_bytes();
+    let body = String::from_utf8_lossy(&bytes).to_string();
+    Ok(ReqResponse {
+        status,
+        replay_nonce,
+        location,
+        body,
+    })
+}
+
+/// Plain `GET`, used for fetching the directory.
+pub async fn get(url: &str) -> Result<ReqResponse> {
+    let req = Request::builder()
+        .method(Method::GET)
+        .uri(url)
+        .body(Full::new(Bytes::new()))
+        .map_err(|e| Error::Call(e.to_string()))?;
+    let res = client().request(req).await?;
+    to_req_response(res).await
+}
+
+/// `HEAD`, used only to fetch a fresh `Replay-Nonce`.
+pub async fn head(url: &str) -> Result<ReqResponse> {
+    let req = Request::builder()
+        .method(Method::HEAD)
+        .uri(url)
+        .body(Full::new(Bytes::new()))
+        .map_err(|e| Error::Call(e.to_string()))?;
+    let res = client().request(req).await?;
+    to_req_response(res).await
+}
+
+/// POST a JWS body with `application/jose+json`, as required for every
+/// authenticated ACME request.
+pub async fn post_jose(url: &str, body: String) -> Result<ReqResponse> {
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri(url)
+        .header("content-type", "application/jose+json")
+        .body(Full::new(Bytes::from(body)))
+        .map_err(|e| Error::Call(e.to_string()))?;
+    let res = client().request(req).await?;
+    to_req_response(res).await
+}
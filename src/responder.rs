@@ -0,0 +1,160 @@
+//! A small, self-contained HTTP-01 challenge responder.
+//!
+//! Opt in with the `http-server` feature. This exists purely as a
+//! convenience for callers who don't already run a web server and don't
+//! want to wire one up just to answer
+//! `/.well-known/acme-challenge/<token>` during validation; see the
+//! top-level example in the crate docs.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+use crate::error::{Error, Result};
+use crate::order::Challenge;
+
+/// A running HTTP-01 responder. Dropping or calling [`Responder::shutdown`]
+/// stops it.
+pub struct Responder {
+    shutdown: Option<oneshot::Sender<()>>,
+    local_addr: SocketAddr,
+}
+
+impl Responder {
+    /// Bind `addr` and start serving `/.well-known/acme-challenge/<token>`
+    /// for each of `challenges`, keyed by [`Challenge::http_token`].
+    pub async fn bind(addr: SocketAddr, challenges: Vec<Challenge>) -> Result<Responder> {
+        let mut proofs = HashMap::new();
+        for chall in challenges {
+            let token = chall.http_token().await;
+            let proof = chall.http_proof().await?;
+            proofs.insert(token, proof);
+        }
+        let proofs = Arc::new(proofs);
+
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| Error::Other(format!("failed to bind {}: {}", addr, e)))?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| Error::Other(e.to_string()))?;
+
+        let (tx, mut rx) = oneshot::channel();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = &mut rx => break,
+                    accepted = listener.accept() => {
+                        let (stream, _) = match accepted {
+                            Ok(s) => s,
+                            Err(_) => continue,
+                        };
+                        let io = TokioIo::new(stream);
+                        let proofs = proofs.clone();
+                        tokio::spawn(async move {
+                            let _ = http1::Builder::new()
+                                .serve_connection(io, service_fn(move |req| {
+                                    let proofs = proofs.clone();
+                                    async move { Ok::<_, Infallible>(respond(req, &proofs)) }
+                                }))
+                                .await;
+                        });
+                    }
+                }
+            }
+        });
+
+        Ok(Responder {
+            shutdown: Some(tx),
+            local_addr,
+        })
+    }
+
+    /// The address actually bound (useful when `addr`'s port was `0`).
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop serving. Safe to call more than once.
+    pub fn shutdown(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+impl Drop for Responder {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn respond(
+    req: Request<Incoming>,
+    proofs: &HashMap<String, String>,
+) -> Response<Full<Bytes>> {
+    respond_path(req.uri().path(), proofs)
+}
+
+/// The part of [`respond`] that doesn't need a live connection to test:
+/// look up the token from the request path and build the response body.
+fn respond_path(path: &str, proofs: &HashMap<String, String>) -> Response<Full<Bytes>> {
+    let prefix = "/.well-known/acme-challenge/";
+    let token = path.strip_prefix(prefix);
+
+    match token.and_then(|t| proofs.get(t)) {
+        Some(proof) => Response::builder()
+            .status(StatusCode::OK)
+            .header("content-type", "text/plain")
+            .body(Full::new(Bytes::from(proof.clone())))
+            .unwrap(),
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Full::new(Bytes::new()))
+            .unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serves_proof_for_known_token() {
+        let mut proofs = HashMap::new();
+        proofs.insert("tok-1".to_string(), "tok-1.thumbprint".to_string());
+
+        let res = respond_path("/.well-known/acme-challenge/tok-1", &proofs);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get("content-type").unwrap(),
+            "text/plain"
+        );
+    }
+
+    #[test]
+    fn not_found_for_unknown_token() {
+        let proofs = HashMap::new();
+        let res = respond_path("/.well-known/acme-challenge/unknown", &proofs);
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn not_found_outside_challenge_path() {
+        let mut proofs = HashMap::new();
+        proofs.insert("tok-1".to_string(), "tok-1.thumbprint".to_string());
+        let res = respond_path("/other", &proofs);
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+}
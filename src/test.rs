@@ -0,0 +1,163 @@
+//! Crate-internal unit tests that don't need network access.
+
+use crate::cert::create_p256_key;
+use crate::jwt::{jwk_thumbprint, key_authorization, sign_jws_hmac};
+use crate::util::{b64, b64_decode, sha256};
+
+#[test]
+fn b64_roundtrips() {
+    let data = b"hello world";
+    let encoded = b64(data);
+    assert_eq!(b64_decode(&encoded).unwrap(), data);
+}
+
+#[test]
+fn b64_has_no_padding() {
+    assert!(!b64(b"a").contains('='));
+}
+
+#[test]
+fn sha256_is_32_bytes() {
+    assert_eq!(sha256(b"hyperacme").unwrap().len(), 32);
+}
+
+#[test]
+fn thumbprint_is_stable_for_same_key() {
+    let pkey = create_p256_key().unwrap();
+    let t1 = jwk_thumbprint(&pkey).unwrap();
+    let t2 = jwk_thumbprint(&pkey).unwrap();
+    assert_eq!(t1, t2);
+}
+
+#[test]
+fn key_authorization_is_token_dot_thumbprint() {
+    let pkey = create_p256_key().unwrap();
+    let ka = key_authorization("abc123", &pkey).unwrap();
+    let thumb = jwk_thumbprint(&pkey).unwrap();
+    assert_eq!(ka, format!("abc123.{}", thumb));
+}
+
+#[test]
+fn eab_jws_signature_matches_hmac_over_protected_dot_payload() {
+    let mac_key = b"external-account-binding-test-key";
+    let protected = serde_json::json!({
+        "alg": "HS256",
+        "kid": "eab-kid-1",
+        "url": "https://acme.test/new-account",
+    });
+    let payload = serde_json::json!({"kty": "EC", "crv": "P-256"});
+
+    let jws = sign_jws_hmac(mac_key, &protected, &payload).unwrap();
+    let v: serde_json::Value = serde_json::from_str(&jws).unwrap();
+    let protected_b64 = v["protected"].as_str().unwrap();
+    let payload_b64 = v["payload"].as_str().unwrap();
+    let signing_input = format!("{}.{}", protected_b64, payload_b64);
+
+    let key = openssl::pkey::PKey::hmac(mac_key).unwrap();
+    let mut signer =
+        openssl::sign::Signer::new(openssl::hash::MessageDigest::sha256(), &key).unwrap();
+    let expected = b64(&signer.sign_oneshot_to_vec(signing_input.as_bytes()).unwrap());
+
+    assert_eq!(v["signature"].as_str().unwrap(), expected);
+}
+
+#[test]
+fn file_persist_round_trips_and_reports_missing_as_none() {
+    use crate::persist::{FilePersist, Persist, PersistKey, PersistKind};
+
+    let dir = std::env::temp_dir().join(format!("hyperacme-test-{}", std::process::id()));
+    let persist = FilePersist::new(&dir);
+    let key = PersistKey::new("acme.test", PersistKind::Certificate, "example.com");
+
+    assert!(persist.get(&key).unwrap().is_none());
+
+    persist.put(&key, b"cert-bytes").unwrap();
+    assert_eq!(persist.get(&key).unwrap().unwrap(), b"cert-bytes");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn certificate_valid_days_left_and_domains_from_self_signed_cert() {
+    use openssl::asn1::Asn1Time;
+    use openssl::hash::MessageDigest;
+    use openssl::x509::extension::SubjectAlternativeName;
+    use openssl::x509::{X509NameBuilder, X509};
+
+    use crate::cert::Certificate;
+
+    let pkey = create_p256_key().unwrap();
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", "example.com").unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder
+        .set_not_before(Asn1Time::days_from_now(0).unwrap().as_ref())
+        .unwrap();
+    builder
+        .set_not_after(Asn1Time::days_from_now(10).unwrap().as_ref())
+        .unwrap();
+    let ctx = builder.x509v3_context(None, None);
+    let san = SubjectAlternativeName::new()
+        .dns("example.com")
+        .build(&ctx)
+        .unwrap();
+    builder.append_extension(san).unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let x509 = builder.build();
+
+    let cert_pem = String::from_utf8(x509.to_pem().unwrap()).unwrap();
+    let cert = Certificate::new(String::new(), cert_pem);
+
+    assert_eq!(cert.domains().unwrap(), vec!["example.com".to_string()]);
+
+    let days_left = cert.valid_days_left().unwrap();
+    assert!(
+        (8..=10).contains(&days_left),
+        "expected ~10 days left, got {days_left}"
+    );
+    assert!(!cert
+        .is_expired_within(std::time::Duration::from_secs(3 * 24 * 60 * 60))
+        .unwrap());
+    assert!(cert
+        .is_expired_within(std::time::Duration::from_secs(20 * 24 * 60 * 60))
+        .unwrap());
+}
+
+#[test]
+fn key_change_inner_jws_carries_new_jwk_and_old_key_payload() {
+    use crate::api::ApiKeyChange;
+    use crate::jwt::{alg, jwk, sign_jws};
+
+    let old_pkey = create_p256_key().unwrap();
+    let new_pkey = create_p256_key().unwrap();
+    let account_url = "https://acme.test/acct/1";
+    let key_change_url = "https://acme.test/key-change";
+
+    let inner_protected = serde_json::json!({
+        "alg": alg(&new_pkey).unwrap(),
+        "jwk": jwk(&new_pkey).unwrap(),
+        "url": key_change_url,
+    });
+    let inner_payload = ApiKeyChange {
+        account: account_url.to_string(),
+        old_key: jwk(&old_pkey).unwrap(),
+    };
+    let inner_jws = sign_jws(&new_pkey, &inner_protected, &inner_payload).unwrap();
+    let v: serde_json::Value = serde_json::from_str(&inner_jws).unwrap();
+
+    let protected: serde_json::Value =
+        serde_json::from_slice(&b64_decode(v["protected"].as_str().unwrap()).unwrap()).unwrap();
+    assert_eq!(protected["url"], key_change_url);
+    assert_eq!(protected["jwk"]["x"], jwk(&new_pkey).unwrap()["x"]);
+
+    let payload: serde_json::Value =
+        serde_json::from_slice(&b64_decode(v["payload"].as_str().unwrap()).unwrap()).unwrap();
+    assert_eq!(payload["account"], account_url);
+    assert_eq!(payload["oldKey"]["x"], jwk(&old_pkey).unwrap()["x"]);
+}
@@ -0,0 +1,121 @@
+//! Account-authenticated transactions against the ACME API.
+//!
+//! This is the one place that knows how to combine a fresh replay-nonce, a
+//! JWS signature and the HTTP layer into a single authenticated call. Every
+//! authenticated request from `acc`, `dir` and `order` goes through a
+//! [`Transaction`]; they call `jwt` directly only for values that end up
+//! inside a payload (key authorizations, the EAB binding and the
+//! key-change inner JWS).
+
+use std::sync::Mutex;
+
+use openssl::pkey::{PKey, Private};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::api::ApiProblem;
+use crate::error::{Error, Result};
+use crate::jwt;
+use crate::req::{self, ReqResponse};
+
+/// Holds the directory's `newNonce` url and the last nonce we were handed,
+/// so callers don't have to round-trip for one on every call.
+pub struct NonceSource {
+    new_nonce_url: String,
+    nonce: Mutex<Option<String>>,
+}
+
+impl NonceSource {
+    pub fn new(new_nonce_url: &str) -> Self {
+        NonceSource {
+            new_nonce_url: new_nonce_url.to_string(),
+            nonce: Mutex::new(None),
+        }
+    }
+
+    async fn fetch(&self) -> Result<String> {
+        let res = req::head(&self.new_nonce_url).await?;
+        res.replay_nonce
+            .ok_or_else(|| Error::Other("server did not return a replay-nonce".into()))
+    }
+
+    /// Take the next nonce to use, fetching a new one if we don't have one
+    /// cached from a previous response.
+    async fn take(&self) -> Result<String> {
+        let cached = self.nonce.lock().unwrap().take();
+        match cached {
+            Some(n) => Ok(n),
+            None => self.fetch().await,
+        }
+    }
+
+    fn stash(&self, res: &ReqResponse) {
+        if let Some(n) = &res.replay_nonce {
+            *self.nonce.lock().unwrap() = Some(n.clone());
+        }
+    }
+}
+
+/// A single account's authenticated transaction log: account key, account
+/// url (once known) and nonce bookkeeping.
+pub struct Transaction<'a> {
+    pub pkey: &'a PKey<Private>,
+    pub account_url: Option<&'a str>,
+    pub nonces: &'a NonceSource,
+}
+
+fn check_status(res: &ReqResponse) -> Result<()> {
+    if res.status >= 400 {
+        let problem: ApiProblem = serde_json::from_str(&res.body).unwrap_or(ApiProblem {
+            typ: None,
+            detail: Some(res.body.clone()),
+            status: Some(res.status),
+        });
+        return Err(Error::Api(problem));
+    }
+    Ok(())
+}
+
+fn protected_header(
+    pkey: &PKey<Private>,
+    account_url: Option<&str>,
+    url: &str,
+    nonce: &str,
+) -> Result<serde_json::Value> {
+    let mut header = json!({
+        "alg": jwt::alg(pkey)?,
+        "nonce": nonce,
+        "url": url,
+    });
+    match account_url {
+        Some(kid) => header["kid"] = json!(kid),
+        None => header["jwk"] = jwt::jwk(pkey)?,
+    }
+    Ok(header)
+}
+
+impl<'a> Transaction<'a> {
+    /// POST a JSON payload to `url`, signed with the account (or, before
+    /// registration, the bare account key).
+    pub async fn post<P: Serialize>(&self, url: &str, payload: &P) -> Result<ReqResponse> {
+        let nonce = self.nonces.take().await?;
+        let protected = protected_header(self.pkey, self.account_url, url, &nonce)?;
+        let body = jwt::sign_jws(self.pkey, &protected, payload)?;
+        let res = req::post_jose(url, body).await?;
+        self.nonces.stash(&res);
+        check_status(&res)?;
+        Ok(res)
+    }
+
+    /// POST-as-GET: an empty-payload signed POST used to fetch a resource
+    /// that requires authentication (RFC 8555 §6.3).
+    pub async fn post_as_get(&self, url: &str) -> Result<ReqResponse> {
+        let nonce = self.nonces.take().await?;
+        let protected = protected_header(self.pkey, self.account_url, url, &nonce)?;
+        let body = jwt::sign_jws_empty(self.pkey, &protected)?;
+        let res = req::post_jose(url, body).await?;
+        self.nonces.stash(&res);
+        check_status(&res)?;
+        Ok(res)
+    }
+}
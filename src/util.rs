@@ -0,0 +1,21 @@
+//! Small helpers shared across the crate.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use openssl::hash::{hash, MessageDigest};
+
+use crate::error::Result;
+
+/// Base64url (no padding) encode, as used throughout JOSE/ACME.
+pub fn b64(data: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(data)
+}
+
+/// Base64url (no padding) decode.
+pub fn b64_decode(data: &str) -> Result<Vec<u8>> {
+    Ok(URL_SAFE_NO_PAD.decode(data)?)
+}
+
+/// SHA-256 digest of `data`.
+pub fn sha256(data: &[u8]) -> Result<Vec<u8>> {
+    Ok(hash(MessageDigest::sha256(), data)?.to_vec())
+}